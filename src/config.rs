@@ -1,3 +1,4 @@
+use crate::output::eprintln_error;
 use clap::ArgMatches;
 use solana_clap_utils::{input_parsers::pubkey_of_signer, keypair::pubkey_from_path};
 use solana_cli_output::OutputFormat;
@@ -22,18 +23,16 @@ impl Config {
         address_name: &str,
         wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
     ) -> Pubkey {
-        if address_name != "owner" {
-            if let Some(address) =
-                pubkey_of_signer(arg_matches, address_name, wallet_manager).unwrap()
-            {
-                return address;
-            }
-        }
+        let explicit_address = if address_name != "owner" {
+            pubkey_of_signer(arg_matches, address_name, wallet_manager).transpose()
+        } else {
+            None
+        };
 
-        return self
-            .default_address(arg_matches, wallet_manager)
+        return explicit_address
+            .unwrap_or_else(|| self.default_address(arg_matches, wallet_manager))
             .unwrap_or_else(|e| {
-                eprintln!("error: {}", e);
+                eprintln_error(&self.output_format, "SignerError", e.to_string());
                 exit(1);
             });
     }
@@ -44,7 +43,7 @@ impl Config {
         wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
     ) -> Result<Pubkey, Box<dyn std::error::Error>> {
         // for backwards compatibility, check owner before cli config default
-        if let Some(address) = pubkey_of_signer(matches, "owner", wallet_manager).unwrap() {
+        if let Some(address) = pubkey_of_signer(matches, "owner", wallet_manager)? {
             return Ok(address);
         }
 
@@ -6,16 +6,18 @@ use spl_associated_token_account::{
     self, create_associated_token_account, get_associated_token_address,
 };
 
+use glob::glob;
 use metaplex_token_metadata::{
     self,
     error::MetadataError,
     instruction::{create_master_edition, create_metadata_accounts, update_metadata_accounts},
     state::{
         Creator, Data, Key, Metadata, EDITION, MAX_CREATOR_LIMIT, MAX_MASTER_EDITION_LEN,
-        MAX_METADATA_LEN, PREFIX,
+        MAX_METADATA_LEN, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH, PREFIX,
     },
     utils::try_from_slice_checked,
 };
+use serde_json::Value;
 use solana_account_decoder::{
     parse_token::{parse_token, TokenAccountType},
     UiAccountEncoding,
@@ -32,6 +34,7 @@ use solana_clap_utils::{
 };
 use solana_cli_output::{CliSignature, OutputFormat};
 use solana_client::{
+    client_error::ClientError,
     rpc_client::RpcClient,
     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
@@ -42,6 +45,7 @@ use solana_sdk::{
     instruction::Instruction,
     message::Message,
     native_token::lamports_to_sol,
+    program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
     signer::{keypair::Keypair, Signer},
@@ -52,19 +56,42 @@ use spl_token::{
     self,
     state::{Account, Mint},
 };
-use std::{fmt::Display, process::exit, str::FromStr, sync::Arc};
+use std::{
+    collections::HashSet, fmt::Display, fs::File, path::Path, process::exit, str::FromStr,
+    sync::Arc,
+};
 
 pub mod config;
 use crate::config::Config;
 
+pub mod metadata_json;
+use metadata_json::{
+    attribute_counts, check_byte_length, check_collection, check_creators, check_images,
+    data_from_metadata_json, fill_template, find_duplicate_assets, preview_collection,
+    validate_metadata_json, ImageLimits,
+};
+
 pub mod output;
-use output::{println_display, CliMetadata, CliMint, CliTokenAmount, UiMetadata};
+use output::{
+    eprintln_error, println_display, CliCollectionCheck, CliHashList, CliMetadata,
+    CliMetadataValidation, CliMetadataValidationReport, CliMint, CliRarityReport, CliSimulation,
+    CliTokenAmount, CliTraitRarity, UiMetadata,
+};
 
 type Error = Box<dyn std::error::Error>;
 type CommandResult = Result<Option<(u64, Vec<Vec<Instruction>>)>, Error>;
 
 // CONSTANTS
 
+// Byte offset of the first creator address in a metadata account: key, update authority,
+// mint, the padded name, symbol and uri with their length prefixes, seller fee basis
+// points, the creators option flag and the creators vec length.
+const FIRST_CREATOR_OFFSET: usize =
+    1 + 32 + 32 + 4 + MAX_NAME_LENGTH + 4 + MAX_SYMBOL_LENGTH + 4 + MAX_URI_LENGTH + 2 + 1 + 4;
+
+// Byte offset of the first creator's verified flag, which follows its address.
+const FIRST_CREATOR_VERIFIED_OFFSET: usize = FIRST_CREATOR_OFFSET + 32;
+
 // INPUT VALIDATORS
 
 fn is_mint_decimals(string: String) -> Result<(), String> {
@@ -96,6 +123,20 @@ where
         })
 }
 
+// Name, symbol and uri are stored in fixed size fields of the metadata account.
+fn is_valid_name(name: String) -> Result<(), String> {
+    check_byte_length("name", &name, MAX_NAME_LENGTH)
+}
+
+fn is_valid_symbol(symbol: String) -> Result<(), String> {
+    check_byte_length("symbol", &symbol, MAX_SYMBOL_LENGTH)
+}
+
+fn is_valid_uri(uri: String) -> Result<(), String> {
+    check_byte_length("uri", &uri, MAX_URI_LENGTH)?;
+    is_url(uri)
+}
+
 // Checks to make sure creator shares sum to 100.
 fn validate_creator_shares(creators: &Vec<Creator>) -> Result<(), clap::Error> {
     let share_sum: u64 = creators.iter().map(|c| c.share as u64).sum();
@@ -118,9 +159,17 @@ fn is_valid_creator<T>(creator: T) -> Result<(), String>
 where
     T: AsRef<str> + Display,
 {
-    let split: Vec<_> = creator.as_ref().split(":").collect();
-    let pubkey_result = split[0].parse::<Pubkey>();
-    let share_result = split[1].parse::<u8>();
+    let (pubkey, share) = match creator.as_ref().split_once(":") {
+        Some(split) => split,
+        None => {
+            return Err(format!(
+                "Creator {} must be of the form <PUBKEY:SHARE>.",
+                creator
+            ))
+        }
+    };
+    let pubkey_result = pubkey.parse::<Pubkey>();
+    let share_result = share.parse::<u8>();
     if let Err(error) = pubkey_result {
         Err(format!("{}", error))
     } else {
@@ -177,6 +226,19 @@ impl FromArgMatches<Data> for Data {
     }
 }
 
+// Reads data from a metadata json file if one was provided, otherwise from the
+// individual metadata arguments.
+fn get_metadata_data(arg_matches: &ArgMatches) -> Result<Data, Error> {
+    match arg_matches.value_of("metadata_file") {
+        Some(path) => {
+            let json: Value = serde_json::from_reader(File::open(path)?)?;
+            let uri = arg_matches.value_of("uri").unwrap_or(&"").to_string();
+            data_from_metadata_json(&json, uri).map_err(|e| e.into())
+        }
+        None => Ok(Data::from_argmatches(arg_matches)),
+    }
+}
+
 struct MintData {
     token: Pubkey,
     mint_authority: Pubkey,
@@ -199,14 +261,20 @@ impl FromArgMatchesConfig<MintData> for MintData {
         config: Option<&Config>,
         wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
     ) -> (Box<dyn Signer>, Self) {
-        let (signer, token) = get_signer(arg_matches, "token_keypair", wallet_manager)
-            .unwrap_or_else(new_throwaway_signer);
+        let config = config.unwrap();
+        let (signer, token) = get_signer(
+            arg_matches,
+            "token_keypair",
+            &config.output_format,
+            wallet_manager,
+        )
+        .unwrap_or_else(new_throwaway_signer);
 
         (
             signer,
             Self {
                 token,
-                mint_authority: config.unwrap().pubkey_or_default(
+                mint_authority: config.pubkey_or_default(
                     arg_matches,
                     "mint_authority",
                     wallet_manager,
@@ -320,12 +388,13 @@ fn new_throwaway_signer() -> (Box<dyn Signer>, Pubkey) {
 fn get_signer(
     matches: &ArgMatches<'_>,
     keypair_name: &str,
+    output_format: &OutputFormat,
     wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
 ) -> Option<(Box<dyn Signer>, Pubkey)> {
     matches.value_of(keypair_name).map(|path| {
         let signer =
             signer_from_path(matches, path, keypair_name, wallet_manager).unwrap_or_else(|e| {
-                eprintln!("error: {}", e);
+                eprintln_error(output_format, "SignerError", e.to_string());
                 exit(1);
             });
         let signer_pubkey = signer.pubkey();
@@ -348,6 +417,21 @@ pub(crate) fn check_fee_payer_balance(config: &Config, required_balance: u64) ->
     }
 }
 
+// Names the broad category of a command error for structured error output.
+fn error_kind(err: &Error) -> &'static str {
+    if err.is::<ClientError>() {
+        "ClientError"
+    } else if err.is::<MetadataError>() {
+        "MetadataError"
+    } else if err.is::<ProgramError>() {
+        "ProgramError"
+    } else if err.is::<clap::Error>() {
+        "InvalidArgument"
+    } else {
+        "Error"
+    }
+}
+
 // CLAP ARGUMENTS
 
 fn generic_address_arg<'a, 'b>() -> Arg<'a, 'b> {
@@ -374,9 +458,21 @@ fn mint_address_arg<'a, 'b>() -> Arg<'a, 'b> {
         .value_name("MINT_ADDRESS")
         .validator(is_valid_pubkey)
         .takes_value(true)
+        .index(1)
+        .required(true)
         .help("Address of the existing mint account.")
 }
 
+fn max_supply_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("max_supply")
+        .long("max-supply")
+        .value_name("MAX_SUPPLY")
+        .takes_value(true)
+        .validator(is_parsable::<u64>)
+        .default_value("1")
+        .help("Specify maximum allowable supply for master edition.")
+}
+
 trait MintArgs {
     fn mint_args(self) -> Self;
 }
@@ -436,6 +532,7 @@ impl MetadataArgs for App<'_, '_> {
                 .global(true)
                 .value_name("NAME")
                 .takes_value(true)
+                .validator(is_valid_name)
                 .help("Specify the name for the mint."),
         )
         .arg(
@@ -443,6 +540,7 @@ impl MetadataArgs for App<'_, '_> {
                 .long("symbol")
                 .value_name("SYMBOL")
                 .takes_value(true)
+                .validator(is_valid_symbol)
                 .help("Specify the symbol for the mint."),
         )
         .arg(
@@ -450,7 +548,7 @@ impl MetadataArgs for App<'_, '_> {
                 .long("uri")
                 .value_name("URI")
                 .takes_value(true)
-                .validator(is_url)
+                .validator(is_valid_uri)
                 .help("Specify the URI for the mint."),
         )
         .arg(
@@ -485,6 +583,19 @@ impl MetadataArgs for App<'_, '_> {
     }
 }
 
+fn metadata_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("metadata_file")
+        .long("metadata-file")
+        .value_name("PATH")
+        .takes_value(true)
+        .conflicts_with_all(&["name", "symbol", "seller_fee_basis_points", "creators"])
+        .requires("uri")
+        .help(
+            "Read name, symbol, seller fee basis points and creators from a \
+            metadata json file. Requires --uri pointing at the uploaded file.",
+        )
+}
+
 fn get_app() -> App<'static, 'static> {
     let app_matches = App::new(crate_name!())
         .about(crate_description!())
@@ -508,6 +619,7 @@ fn get_app() -> App<'static, 'static> {
             Arg::with_name("json_rpc_url")
                 .short("u")
                 .long("url")
+                .visible_alias("cluster")
                 .value_name("URL_OR_MONIKER")
                 .takes_value(true)
                 .global(true)
@@ -518,6 +630,19 @@ fn get_app() -> App<'static, 'static> {
                 Default from the configuration file.",
                 ),
         )
+        .arg(
+            Arg::with_name("keypair")
+                .short("k")
+                .long("keypair")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .validator(is_valid_signer)
+                .help(
+                    "Filepath or URL to the Solana keypair used as the default fee payer \
+                    and authority. Default from the configuration file.",
+                ),
+        )
         .arg(
             Arg::with_name("verbose")
                 .short("v")
@@ -554,12 +679,187 @@ fn get_app() -> App<'static, 'static> {
                 .arg(generic_address_arg()),
         )
         .subcommand(SubCommand::with_name("filter").arg(generic_address_arg()))
+        .subcommand(
+            SubCommand::with_name("validate-metadata")
+                .about("Validate metadata json files against the Metaplex token metadata standard.")
+                .arg(
+                    Arg::with_name("glob")
+                        .value_name("GLOB")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("Glob pattern matching metadata json files, e.g. \"assets/*.json\"."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("check-collection")
+                .about(
+                    "Check that a collection directory of <INDEX>.<EXT> assets and \
+                    <INDEX>.json metadata files is complete and consistent.",
+                )
+                .arg(
+                    Arg::with_name("assets_dir")
+                        .value_name("ASSETS_DIR")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("Directory containing the collection assets and metadata files."),
+                )
+                .arg(
+                    Arg::with_name("strict")
+                        .long("strict")
+                        .takes_value(false)
+                        .help("Fail on warnings, such as assets with identical content."),
+                )
+                .arg(
+                    Arg::with_name("check_images")
+                        .long("check-images")
+                        .takes_value(false)
+                        .help(
+                            "Also check that images match their extension, decode without \
+                            errors and share the same aspect ratio.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("max_image_width")
+                        .long("max-image-width")
+                        .value_name("PIXELS")
+                        .takes_value(true)
+                        .requires("check_images")
+                        .validator(is_parsable::<usize>)
+                        .help("Maximum image width in pixels."),
+                )
+                .arg(
+                    Arg::with_name("max_image_height")
+                        .long("max-image-height")
+                        .value_name("PIXELS")
+                        .takes_value(true)
+                        .requires("check_images")
+                        .validator(is_parsable::<usize>)
+                        .help("Maximum image height in pixels."),
+                )
+                .arg(
+                    Arg::with_name("max_image_bytes")
+                        .long("max-image-bytes")
+                        .value_name("BYTES")
+                        .takes_value(true)
+                        .requires("check_images")
+                        .validator(is_parsable::<u64>)
+                        .help("Maximum image file size in bytes."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("preview")
+                .about(
+                    "Summarize a collection directory: item count, bytes by media type, \
+                    attribute distribution and sample metadata.",
+                )
+                .arg(
+                    Arg::with_name("assets_dir")
+                        .value_name("ASSETS_DIR")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("Directory containing the collection assets and metadata files."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rarity-report")
+                .about("Report trait counts and rarity percentages across metadata json files.")
+                .arg(
+                    Arg::with_name("glob")
+                        .value_name("GLOB")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("Glob pattern matching metadata json files, e.g. \"assets/*.json\"."),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .long("csv")
+                        .takes_value(false)
+                        .help("Print the report as csv instead of the --output format."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("snapshot-hashlist")
+                .about(
+                    "List the mint addresses of all metadata accounts with the given verified \
+                    first creator, e.g. a candy machine.",
+                )
+                .arg(
+                    Arg::with_name("creator")
+                        .value_name("CREATOR_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help(
+                            "Address of the first creator. For a v1 candy machine this is the \
+                            candy machine id, for v2 it is the candy machine creator PDA derived \
+                            from the id.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Write the hash list json to a file instead of printing it."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("generate-metadata")
+                .about(
+                    "Generate <INDEX>.json metadata files from a metadata json template and \
+                    a csv table with one row per item.",
+                )
+                .arg(
+                    Arg::with_name("template")
+                        .long("template")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help(
+                            "Metadata json template. Strings can reference {index} and any \
+                            csv column as {<COLUMN>}.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("table")
+                        .long("table")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help(
+                            "Csv table with a header row. Columns not referenced by the \
+                            template are added as attributes.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("out_dir")
+                        .long("out-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .default_value(".")
+                        .help("Directory to write the metadata files to."),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .takes_value(false)
+                        .help(
+                            "Overwrite metadata files that already exist in the output directory.",
+                        ),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("metadata-create")
                 .about("Create metadata account for existing token mint.")
                 .arg(mint_address_arg())
                 .arg(update_authority_arg())
-                .metadata_args(),
+                .metadata_args()
+                .arg(metadata_file_arg()),
         )
         .subcommand(
             SubCommand::with_name("metadata-update")
@@ -605,16 +905,16 @@ fn get_app() -> App<'static, 'static> {
                 )
                 .mint_args()
                 .metadata_args()
+                .arg(metadata_file_arg())
                 .arg(update_authority_arg())
-                .arg(
-                    Arg::with_name("max_supply")
-                        .long("max-supply")
-                        .value_name("MAX_SUPPLY")
-                        .takes_value(true)
-                        .validator(is_parsable::<u64>)
-                        .default_value("1")
-                        .help("Specify maximum allowable supply for master edition."),
-                ),
+                .arg(max_supply_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("master-edition-create")
+                .about("Create master edition account for existing token mint and metadata.")
+                .arg(mint_address_arg())
+                .arg(update_authority_arg())
+                .arg(max_supply_arg()),
         )
         .subcommand(
             SubCommand::with_name("mint-create")
@@ -637,6 +937,49 @@ fn get_app() -> App<'static, 'static> {
     app_matches
 }
 
+// Runs commands that only read and write local files. They don't need a keypair or a cluster
+// connection, so they run before the config is built. Returns None for other commands.
+fn run_local_command(
+    sub_command: &str,
+    arg_matches: &ArgMatches,
+    output_format: &OutputFormat,
+) -> Option<CommandResult> {
+    let result = match sub_command {
+        "validate-metadata" => {
+            let pattern = arg_matches.value_of("glob").unwrap();
+            command_validate_metadata(output_format, pattern)
+        }
+        "check-collection" => {
+            let assets_dir = arg_matches.value_of("assets_dir").unwrap();
+            let image_limits = arg_matches.is_present("check_images").then(|| ImageLimits {
+                max_width: value_of::<usize>(arg_matches, "max_image_width"),
+                max_height: value_of::<usize>(arg_matches, "max_image_height"),
+                max_bytes: value_of::<u64>(arg_matches, "max_image_bytes"),
+            });
+            let strict = arg_matches.is_present("strict");
+            command_check_collection(output_format, Path::new(assets_dir), image_limits, strict)
+        }
+        "preview" => {
+            let assets_dir = arg_matches.value_of("assets_dir").unwrap();
+            command_preview(output_format, Path::new(assets_dir))
+        }
+        "rarity-report" => {
+            let pattern = arg_matches.value_of("glob").unwrap();
+            let csv = arg_matches.is_present("csv");
+            command_rarity_report(output_format, pattern, csv)
+        }
+        "generate-metadata" => {
+            let template = arg_matches.value_of("template").unwrap();
+            let table = arg_matches.value_of("table").unwrap();
+            let out_dir = arg_matches.value_of("out_dir").unwrap();
+            let force = arg_matches.is_present("force");
+            command_generate_metadata(output_format, template, table, Path::new(out_dir), force)
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
 #[tokio::main]
 async fn main() {
     let no_wait = false;
@@ -648,6 +991,29 @@ async fn main() {
     let (sub_command, sub_matches) = app_matches.subcommand();
     let matches = sub_matches.unwrap();
 
+    // Parsed before the signer so that keypair errors respect the output format.
+    let verbose = matches.is_present("verbose");
+    let output_format = matches
+        .value_of("output_format")
+        .map(|value| match value {
+            "json" => OutputFormat::Json,
+            "json-compact" => OutputFormat::JsonCompact,
+            _ => unreachable!(),
+        })
+        .unwrap_or(if verbose {
+            OutputFormat::DisplayVerbose
+        } else {
+            OutputFormat::Display
+        });
+
+    if let Some(result) = run_local_command(sub_command, matches, &output_format) {
+        if let Err(err) = result {
+            eprintln_error(&output_format, error_kind(&err), err.to_string());
+            exit(1);
+        }
+        return;
+    }
+
     let config = {
         let cli_config = if let Some(config_file) = matches.value_of("config_file") {
             solana_cli_config::Config::load(config_file).unwrap_or_default()
@@ -660,11 +1026,14 @@ async fn main() {
                 .unwrap_or(&cli_config.json_rpc_url),
         );
 
+        let keypair_path = matches
+            .value_of("keypair")
+            .map(|p| p.to_string())
+            .unwrap_or(cli_config.keypair_path);
+
         let (signer, fee_payer) = signer_from_path(
             matches,
-            matches
-                .value_of("fee_payer")
-                .unwrap_or(&cli_config.keypair_path),
+            matches.value_of("fee_payer").unwrap_or(&keypair_path),
             "fee_payer",
             &mut wallet_manager,
         )
@@ -673,32 +1042,18 @@ async fn main() {
             (s, p)
         })
         .unwrap_or_else(|e| {
-            eprintln!("error: {}", e);
+            eprintln_error(&output_format, "SignerError", e.to_string());
             exit(1);
         });
         bulk_signers.push(signer);
 
-        let verbose = matches.is_present("verbose");
-        let output_format = matches
-            .value_of("output_format")
-            .map(|value| match value {
-                "json" => OutputFormat::Json,
-                "json-compact" => OutputFormat::JsonCompact,
-                _ => unreachable!(),
-            })
-            .unwrap_or(if verbose {
-                OutputFormat::DisplayVerbose
-            } else {
-                OutputFormat::Display
-            });
-
         let dry_run = matches.is_present("dry_run");
 
         Config {
             rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
             output_format,
             fee_payer,
-            default_keypair_path: cli_config.keypair_path,
+            default_keypair_path: keypair_path,
             dry_run,
         }
     };
@@ -718,22 +1073,27 @@ async fn main() {
             let address = pubkey_of(arg_matches, "address").unwrap();
             get_filtered_program_accounts(&config, address)
         }
+        ("snapshot-hashlist", Some(arg_matches)) => {
+            let creator = pubkey_of(arg_matches, "creator").unwrap();
+            let out = arg_matches.value_of("out");
+            command_snapshot_hashlist(&config, creator, out)
+        }
         ("metadata-create", Some(arg_matches)) => {
             let mint_address = pubkey_of(arg_matches, "mint_address").unwrap();
             let update_authority =
                 config.pubkey_or_default(arg_matches, "update_authority", &mut wallet_manager);
             let is_mutable = !arg_matches.is_present("immutable");
 
-            let data = Data::from_argmatches(&arg_matches);
-
-            command_metadata_create(
-                &config,
-                mint_address,
-                update_authority,
-                is_mutable,
-                data,
-                None,
-            )
+            get_metadata_data(&arg_matches).and_then(|data| {
+                command_metadata_create(
+                    &config,
+                    mint_address,
+                    update_authority,
+                    is_mutable,
+                    data,
+                    None,
+                )
+            })
         }
         ("metadata-update", Some(arg_matches)) => {
             let address = pubkey_of(arg_matches, "address").unwrap();
@@ -772,24 +1132,31 @@ async fn main() {
             let update_authority =
                 config.pubkey_or_default(arg_matches, "update_authority", &mut wallet_manager);
             let is_mutable = !arg_matches.is_present("immutable");
-            let metadata_data = Data::from_argmatches(&arg_matches);
+            let max_supply = value_t!(arg_matches, "max_supply", u64).ok();
 
+            get_metadata_data(&arg_matches).and_then(|metadata_data| {
+                command_nft_create(
+                    &config,
+                    mint_data,
+                    update_authority,
+                    is_mutable,
+                    metadata_data,
+                    max_supply,
+                )
+            })
+        }
+        ("master-edition-create", Some(arg_matches)) => {
+            let mint_address = pubkey_of(arg_matches, "mint_address").unwrap();
+            let update_authority =
+                config.pubkey_or_default(arg_matches, "update_authority", &mut wallet_manager);
             let max_supply = value_t!(arg_matches, "max_supply", u64).ok();
 
-            command_nft_create(
-                &config,
-                mint_data,
-                update_authority,
-                is_mutable,
-                metadata_data,
-                max_supply,
-            )
+            command_master_edition_create(&config, mint_address, update_authority, None, max_supply)
         }
         ("mint-supply", Some(arg_matches)) => {
-            let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
-                .unwrap()
-                .unwrap();
-            command_supply(&config, address)
+            pubkey_of_signer(arg_matches, "address", &mut wallet_manager).and_then(|address| {
+                command_supply(&config, address.ok_or("Token address is required.")?)
+            })
         }
         ("mint-create", Some(arg_matches)) => {
             let (signer, data) =
@@ -810,16 +1177,67 @@ async fn main() {
                 signers: bulk_signers,
             };
             let (recent_blockhash, fee_calculator) = config.rpc_client.get_recent_blockhash()?;
-            for instructions in instruction_batches {
-                let message = Message::new(&instructions, fee_payer);
-                check_fee_payer_balance(
-                    &config,
-                    minimum_balance_for_rent_exemption + fee_calculator.calculate_fee(&message),
-                )?;
+            let mut written_accounts = Vec::<HashSet<Pubkey>>::new();
+            let mut failed_simulations = 0;
+            for (index, instructions) in instruction_batches.iter().enumerate() {
+                let message = Message::new(instructions, fee_payer);
+                let fee = fee_calculator.calculate_fee(&message);
+                let balance_check =
+                    check_fee_payer_balance(&config, minimum_balance_for_rent_exemption + fee);
                 let signers = signer_info.signers_for_message(&message);
                 let mut transaction = Transaction::new_unsigned(message);
 
                 transaction.try_sign(&signers, recent_blockhash)?;
+                if config.dry_run {
+                    // Simulation doesn't persist accounts, so a batch using accounts written
+                    // by an earlier one, e.g. a mint created by the same command, is listed
+                    // but not simulated.
+                    let accounts = || {
+                        instructions
+                            .iter()
+                            .flat_map(|i| i.accounts.iter())
+                            .filter(|a| a.pubkey != config.fee_payer)
+                    };
+                    let used: HashSet<Pubkey> = accounts().map(|a| a.pubkey).collect();
+                    let depends_on: Vec<usize> = written_accounts
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, written)| !written.is_disjoint(&used))
+                        .map(|(earlier, _)| earlier)
+                        .collect();
+                    written_accounts.push(
+                        accounts()
+                            .filter(|a| a.is_writable)
+                            .map(|a| a.pubkey)
+                            .collect(),
+                    );
+
+                    let mut simulation = CliSimulation {
+                        transaction: index,
+                        program_ids: instructions
+                            .iter()
+                            .map(|i| i.program_id.to_string())
+                            .collect(),
+                        fee,
+                        depends_on,
+                        error: None,
+                        logs: vec![],
+                    };
+                    // An unfunded fee payer fails the transaction without ending the listing.
+                    if let Err(error) = balance_check {
+                        simulation.error = Some(error.to_string());
+                    } else if simulation.depends_on.is_empty() {
+                        let result = config.rpc_client.simulate_transaction(&transaction)?.value;
+                        simulation.error = result.err.map(|e| e.to_string());
+                        simulation.logs = result.logs.unwrap_or_default();
+                    }
+                    if simulation.error.is_some() {
+                        failed_simulations += 1;
+                    }
+                    println!("{}", config.output_format.formatted_string(&simulation));
+                    continue;
+                }
+                balance_check?;
                 let signature = if no_wait {
                     config.rpc_client.send_transaction(&transaction)?
                 } else {
@@ -832,11 +1250,16 @@ async fn main() {
                 };
                 println!("{}", config.output_format.formatted_string(&signature));
             }
+            if failed_simulations > 0 {
+                return Err(
+                    format!("{} transactions failed the dry run.", failed_simulations).into(),
+                );
+            }
         }
         Ok(())
     })
     .map_err(|err| {
-        eprintln!("{}", err);
+        eprintln_error(&config.output_format, error_kind(&err), err.to_string());
         exit(1);
     });
 }
@@ -881,12 +1304,61 @@ fn get_filtered_program_accounts(config: &Config, address: Pubkey) -> CommandRes
     let accounts = config
         .rpc_client
         .get_program_accounts_with_config(&metaplex_token_metadata::id(), method_config)
-        .map_err(|_| format!("Could not find metadata account {}", address))
-        .unwrap();
+        .map_err(|_| format!("Could not find metadata account {}", address))?;
     println!("{:?}", accounts);
     Ok(None)
 }
 
+// Finds metadata accounts by verified first creator. Candy machines are set as the verified
+// first creator of every nft they mint, so this produces the hash list for a candy machine.
+fn command_snapshot_hashlist(config: &Config, creator: Pubkey, out: Option<&str>) -> CommandResult {
+    let method_config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp {
+                offset: FIRST_CREATOR_OFFSET,
+                bytes: MemcmpEncodedBytes::Base58(creator.to_string()),
+                encoding: None,
+            }),
+            // Anyone can list an address as an unverified creator, so only count mints where
+            // the creator signed.
+            RpcFilterType::Memcmp(Memcmp {
+                offset: FIRST_CREATOR_VERIFIED_OFFSET,
+                bytes: MemcmpEncodedBytes::Bytes(vec![1]),
+                encoding: None,
+            }),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: None,
+            commitment: Some(config.rpc_client.commitment()),
+        },
+        with_context: Some(false),
+    };
+    let accounts = config
+        .rpc_client
+        .get_program_accounts_with_config(&metaplex_token_metadata::id(), method_config)?;
+
+    let mut mints: Vec<String> = accounts
+        .iter()
+        .filter_map(|(_, account)| parse_metadata_account(&account.data).ok())
+        .map(|metadata| metadata.mint.to_string())
+        .collect();
+    mints.sort();
+    let hash_list = CliHashList { mints };
+
+    match out {
+        Some(path) => {
+            serde_json::to_writer_pretty(File::create(path)?, &hash_list)?;
+            println_display(
+                &config.output_format,
+                format!("Wrote {} mint addresses to {}", hash_list.mints.len(), path),
+            );
+        }
+        None => println!("{}", config.output_format.formatted_string(&hash_list)),
+    }
+    Ok(None)
+}
+
 fn command_metadata_info(config: &Config, address: Pubkey) -> CommandResult {
     let (address, metadata) = Metadata::fetch_and_parse(config, &address)?;
     let cli_metadata = parse_cli_metadata(address, metadata);
@@ -894,6 +1366,157 @@ fn command_metadata_info(config: &Config, address: Pubkey) -> CommandResult {
     Ok(None)
 }
 
+fn command_validate_metadata(output_format: &OutputFormat, pattern: &str) -> CommandResult {
+    let mut files = Vec::<CliMetadataValidation>::new();
+    for entry in glob(pattern)? {
+        let path = entry?;
+        let issues = match serde_json::from_reader::<_, Value>(File::open(&path)?) {
+            Ok(json) => validate_metadata_json(&json),
+            Err(error) => vec![format!("Unable to parse json: {}", error)],
+        };
+        files.push(CliMetadataValidation {
+            path: path.display().to_string(),
+            issues,
+        });
+    }
+    if files.is_empty() {
+        return Err(format!("No metadata files match {}.", pattern).into());
+    }
+
+    let invalid = files.iter().filter(|f| !f.issues.is_empty()).count();
+    let report = CliMetadataValidationReport {
+        valid: files.len() - invalid,
+        invalid,
+        files,
+    };
+    println!("{}", output_format.formatted_string(&report));
+
+    if report.invalid > 0 {
+        Err(format!(
+            "{} of {} metadata files failed validation.",
+            report.invalid,
+            report.valid + report.invalid
+        )
+        .into())
+    } else {
+        Ok(None)
+    }
+}
+
+fn command_check_collection(
+    output_format: &OutputFormat,
+    assets_dir: &Path,
+    image_limits: Option<ImageLimits>,
+    strict: bool,
+) -> CommandResult {
+    let (items, mut issues) = check_collection(assets_dir)?;
+    if let Some(image_limits) = image_limits {
+        issues.extend(check_images(assets_dir, &image_limits)?);
+    }
+    let warnings = find_duplicate_assets(assets_dir)?;
+    let check = CliCollectionCheck {
+        items,
+        issues,
+        warnings,
+    };
+    println!("{}", output_format.formatted_string(&check));
+
+    let failures = check.issues.len() + if strict { check.warnings.len() } else { 0 };
+    if failures == 0 {
+        Ok(None)
+    } else {
+        Err(format!("Found {} issues in {}.", failures, assets_dir.display()).into())
+    }
+}
+
+fn command_preview(output_format: &OutputFormat, assets_dir: &Path) -> CommandResult {
+    let preview = preview_collection(assets_dir)?;
+    println!("{}", output_format.formatted_string(&preview));
+    Ok(None)
+}
+
+fn command_rarity_report(output_format: &OutputFormat, pattern: &str, csv: bool) -> CommandResult {
+    let mut jsons = Vec::<Value>::new();
+    for entry in glob(pattern)? {
+        let path = entry?;
+        let json = serde_json::from_reader(File::open(&path)?)
+            .map_err(|e| format!("Unable to parse {}: {}", path.display(), e))?;
+        jsons.push(json);
+    }
+    if jsons.is_empty() {
+        return Err(format!("No metadata files match {}.", pattern).into());
+    }
+
+    let items = jsons.len();
+    let mut traits: Vec<CliTraitRarity> = attribute_counts(&jsons)
+        .into_iter()
+        .flat_map(|(trait_type, values)| {
+            values
+                .into_iter()
+                .map(move |(value, count)| CliTraitRarity {
+                    trait_type: trait_type.clone(),
+                    value,
+                    count,
+                    percentage: count as f64 / items as f64 * 100.0,
+                })
+        })
+        .collect();
+    // Rarest values first within each trait type.
+    traits.sort_by(|a, b| a.trait_type.cmp(&b.trait_type).then(a.count.cmp(&b.count)));
+
+    let report = CliRarityReport { items, traits };
+    if csv {
+        report.write_csv(std::io::stdout())?;
+    } else {
+        println!("{}", output_format.formatted_string(&report));
+    }
+    Ok(None)
+}
+
+fn command_generate_metadata(
+    output_format: &OutputFormat,
+    template: &str,
+    table: &str,
+    out_dir: &Path,
+    force: bool,
+) -> CommandResult {
+    let template: Value = serde_json::from_reader(File::open(template)?)?;
+    let mut reader = csv::Reader::from_path(table)?;
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+    let rows = reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    // Check every output path before writing so a refused run leaves no partial output.
+    let paths: Vec<_> = (0..rows.len())
+        .map(|index| out_dir.join(format!("{}.json", index)))
+        .collect();
+    if !force {
+        if let Some(existing) = paths.iter().find(|path| path.exists()) {
+            return Err(format!(
+                "{} already exists. Use --force to overwrite existing metadata files.",
+                existing.display()
+            )
+            .into());
+        }
+    }
+
+    std::fs::create_dir_all(out_dir)?;
+    for (index, (record, path)) in rows.iter().zip(&paths).enumerate() {
+        let row: Vec<String> = record.iter().map(|c| c.to_string()).collect();
+        let json = fill_template(&template, &headers, &row, index);
+        serde_json::to_writer_pretty(File::create(path)?, &json)?;
+    }
+
+    println_display(
+        output_format,
+        format!(
+            "Wrote {} metadata files to {}",
+            rows.len(),
+            out_dir.display()
+        ),
+    );
+    Ok(None)
+}
+
 fn command_metadata_create(
     config: &Config,
     mint_address: Pubkey,
@@ -906,6 +1529,7 @@ fn command_metadata_create(
         if let Err(error) = validate_creator_shares(creators) {
             return Err(error.into());
         }
+        check_creators(creators)?;
     }
 
     let metadata_address = Metadata::calc_associated_address(&mint_address, None);
@@ -918,7 +1542,8 @@ fn command_metadata_create(
         Some(mint_data) => mint_data.mint_authority,
         None => {
             let (_, mint) = Mint::fetch_and_parse(config, &mint_address)?;
-            mint.mint_authority.unwrap()
+            mint.mint_authority
+                .ok_or_else(|| format!("Mint {} has no mint authority.", mint_address))?
         }
     };
 
@@ -926,7 +1551,10 @@ fn command_metadata_create(
     // case a signature from the update authority is required.
     let update_authority_is_signer = &mint_authority != &config.fee_payer;
 
-    println_display(config, format!("Creating metadata {}", metadata_address));
+    println_display(
+        &config.output_format,
+        format!("Creating metadata {}", metadata_address),
+    );
 
     let instructions = vec![create_metadata_accounts(
         metaplex_token_metadata::id(),
@@ -968,7 +1596,7 @@ fn command_metadata_update_account(
     }
 
     println_display(
-        config,
+        &config.output_format,
         format!(
             "Updating metadata:\n  Metadata: {}\n  Mint: {}",
             metadata_address, metadata.mint
@@ -1002,6 +1630,7 @@ fn command_metadata_update_account(
         if let Err(error) = validate_creator_shares(&creators) {
             return Err(error.into());
         } else {
+            check_creators(&creators)?;
             metadata.data.creators = Some(creators);
             is_new_data = true;
         }
@@ -1032,23 +1661,38 @@ fn command_metadata_update_account(
 
 fn command_master_edition_create(
     config: &Config,
+    mint_address: Pubkey,
     update_authority: Pubkey,
-    metadata_address: Pubkey,
-    mint_data: &MintData,
+    mint_data: Option<&MintData>,
     max_supply: Option<u64>,
 ) -> CommandResult {
     let minimum_balance_for_rent_exemption = config
         .rpc_client
         .get_minimum_balance_for_rent_exemption(MAX_MASTER_EDITION_LEN)?;
 
-    let edition = Mint::calc_master_edition(&mint_data.token);
+    let metadata_address = Metadata::calc_associated_address(&mint_address, None);
+    let edition = Mint::calc_master_edition(&mint_address);
+
+    let mint_authority = match mint_data {
+        Some(mint_data) => mint_data.mint_authority,
+        None => {
+            let (_, mint) = Mint::fetch_and_parse(config, &mint_address)?;
+            mint.mint_authority
+                .ok_or_else(|| format!("Mint {} has no mint authority.", mint_address))?
+        }
+    };
+
+    println_display(
+        &config.output_format,
+        format!("Creating master edition {}", edition),
+    );
 
     let instructions = vec![create_master_edition(
         metaplex_token_metadata::id(),
         edition,
-        mint_data.token,
+        mint_address,
         update_authority,
-        mint_data.mint_authority,
+        mint_authority,
         metadata_address,
         config.fee_payer,
         max_supply,
@@ -1112,19 +1756,22 @@ fn command_nft_create(
     results.push(result);
 
     // Create master edition for mint
-    let metadata_address = Metadata::calc_associated_address(&mint_data.token, None);
     let result = command_master_edition_create(
         &config,
+        mint_data.token,
         update_authority,
-        metadata_address,
-        &mint_data,
+        Some(&mint_data),
         max_supply,
     );
     results.push(result);
 
     //Collect results
-    let (total_min_balance, instruction_batches): (u64, Vec<Vec<Instruction>>) =
-        results.into_iter().map(|r| r.unwrap().unwrap()).fold(
+    let (total_min_balance, instruction_batches): (u64, Vec<Vec<Instruction>>) = results
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .fold(
             (0, Vec::<Vec<Instruction>>::new()),
             |(bal_a, mut ins_a), (bal_b, ins_b)| {
                 (bal_a + bal_b, {
@@ -1138,7 +1785,10 @@ fn command_nft_create(
 }
 
 fn command_create_token(config: &Config, data: &MintData) -> CommandResult {
-    println_display(config, format!("Creating token {}", data.token));
+    println_display(
+        &config.output_format,
+        format!("Creating token {}", data.token),
+    );
 
     let minimum_balance_for_rent_exemption = config
         .rpc_client
@@ -1197,7 +1847,10 @@ fn command_create_token_account(
         .get_minimum_balance_for_rent_exemption(Account::LEN)?;
 
     let (account, system_account_ok, instructions) = if let Some(account) = maybe_account {
-        println_display(config, format!("Creating account {}", account));
+        println_display(
+            &config.output_format,
+            format!("Creating account {}", account),
+        );
         (
             account,
             false,
@@ -1219,7 +1872,10 @@ fn command_create_token_account(
         )
     } else {
         let account = get_associated_token_address(&owner, &token);
-        println_display(config, format!("Creating account {}", account));
+        println_display(
+            &config.output_format,
+            format!("Creating account {}", account),
+        );
         (
             account,
             true,
@@ -1257,7 +1913,7 @@ fn command_mint(
     use_unchecked_instruction: bool,
 ) -> CommandResult {
     println_display(
-        config,
+        &config.output_format,
         format!(
             "Minting {} tokens\n  Token: {}\n  Recipient: {}",
             ui_amount, token, recipient
@@ -1299,8 +1955,16 @@ fn command_mint(
 
 #[cfg(test)]
 mod cli_tests {
-    use super::{get_app, get_creators_vec, validate_creator_shares};
+    use super::{
+        get_app, get_creators_vec, run_local_command, validate_creator_shares,
+        FIRST_CREATOR_OFFSET, FIRST_CREATOR_VERIFIED_OFFSET,
+    };
+    use borsh::BorshSerialize;
     use clap::ErrorKind;
+    use metaplex_token_metadata::state::{
+        Creator, Data, Key, Metadata, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+    };
+    use solana_cli_output::OutputFormat;
     use solana_sdk::{
         pubkey::Pubkey,
         signer::{keypair::Keypair, Signer},
@@ -1394,6 +2058,170 @@ mod cli_tests {
         assert_eq!(res.unwrap_err().kind, ErrorKind::ValueValidation);
     }
 
+    #[test]
+    // It fails if a metadata file is combined with individual metadata values.
+    fn metadata_create_metadata_file_conflicts() {
+        let test_pubkey: Pubkey = Keypair::new().pubkey();
+        let res = get_app().get_matches_from_safe(vec![
+            "testeroni",
+            "metadata-create",
+            &test_pubkey.to_string(),
+            "--metadata-file",
+            "0.json",
+            "--name",
+            "yo yo",
+        ]);
+
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().kind, ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    // It runs local commands without loading the keypair.
+    fn local_command_missing_keypair() {
+        let dir = std::env::temp_dir().join(format!("local_{}", Keypair::new().pubkey()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let m = get_app().get_matches_from(vec![
+            "testeroni",
+            "preview",
+            dir.to_str().unwrap(),
+            "--keypair",
+            "/nonexistent/id.json",
+        ]);
+        let (sub_command, sub_m) = m.subcommand();
+        let result = run_local_command(sub_command, sub_m.unwrap(), &OutputFormat::Display);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(result, Some(Ok(None))));
+    }
+
+    #[test]
+    // It requires a uri alongside a metadata file.
+    fn metadata_create_metadata_file_requires_uri() {
+        let test_pubkey: Pubkey = Keypair::new().pubkey();
+        let res = get_app().get_matches_from_safe(vec![
+            "testeroni",
+            "metadata-create",
+            &test_pubkey.to_string(),
+            "--metadata-file",
+            "0.json",
+        ]);
+
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().kind, ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    // It accepts a cluster moniker and keypair path as global options.
+    fn cluster_and_keypair() {
+        let test_pubkey: Pubkey = Keypair::new().pubkey();
+        let m = get_app().get_matches_from(vec![
+            "testeroni",
+            "metadata-info",
+            &test_pubkey.to_string(),
+            "--cluster",
+            "devnet",
+            "--keypair",
+            &test_pubkey.to_string(),
+        ]);
+        let sub_m = m.subcommand_matches("metadata-info").unwrap();
+        assert_eq!(sub_m.value_of("json_rpc_url").unwrap(), "devnet");
+        assert_eq!(sub_m.value_of("keypair").unwrap(), test_pubkey.to_string());
+    }
+
+    #[test]
+    // It fails if a creator has no share.
+    fn metadata_create_creators_missing_share() {
+        let test_pubkey: Pubkey = Keypair::new().pubkey();
+        let res = get_app().get_matches_from_safe(vec![
+            "testeroni",
+            "metadata-create",
+            &test_pubkey.to_string(),
+            "--uri",
+            "ifps://testeroni",
+            "--creators",
+            &test_pubkey.to_string(),
+        ]);
+
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().kind, ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    // It requires a mint address for commands on an existing mint.
+    fn mint_address_required() {
+        for sub_command in ["mint-info", "metadata-create"] {
+            let res = get_app().get_matches_from_safe(vec!["testeroni", sub_command]);
+            assert_eq!(res.unwrap_err().kind, ErrorKind::MissingRequiredArgument);
+        }
+    }
+
+    #[test]
+    // It requires a mint address and defaults max supply to a one-of-one.
+    fn master_edition_create_max_supply() {
+        let test_pubkey: Pubkey = Keypair::new().pubkey();
+        let res = get_app().get_matches_from_safe(vec!["testeroni", "master-edition-create"]);
+        assert_eq!(res.unwrap_err().kind, ErrorKind::MissingRequiredArgument);
+
+        let m = get_app().get_matches_from(vec![
+            "testeroni",
+            "master-edition-create",
+            &test_pubkey.to_string(),
+        ]);
+        let sub_m = m.subcommand_matches("master-edition-create").unwrap();
+        assert_eq!(sub_m.value_of("max_supply"), Some("1"));
+    }
+
+    #[test]
+    // It fails if the name is longer than the metadata account allows.
+    fn metadata_create_name_too_long() {
+        let test_pubkey: Pubkey = Keypair::new().pubkey();
+        let res = get_app().get_matches_from_safe(vec![
+            "testeroni",
+            "metadata-create",
+            &test_pubkey.to_string(),
+            "--name",
+            &"x".repeat(33),
+        ]);
+
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().kind, ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    // It matches the first creator address and verified flag offsets of a metadata account.
+    fn first_creator_offset() {
+        let creator: Pubkey = Keypair::new().pubkey();
+        // The program pads strings to their maximum length.
+        let puffed_out_string =
+            |s: &str, size: usize| format!("{}{}", s, "\0".repeat(size - s.len()));
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::default(),
+            mint: Pubkey::default(),
+            data: Data {
+                name: puffed_out_string("testeroni", MAX_NAME_LENGTH),
+                symbol: puffed_out_string("TEST", MAX_SYMBOL_LENGTH),
+                uri: puffed_out_string("ipfs://testeroni", MAX_URI_LENGTH),
+                seller_fee_basis_points: 0,
+                creators: Some(vec![Creator {
+                    address: creator,
+                    verified: true,
+                    share: 100,
+                }]),
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+        };
+        let data = metadata.try_to_vec().unwrap();
+        assert_eq!(
+            &data[FIRST_CREATOR_OFFSET..FIRST_CREATOR_OFFSET + 32],
+            creator.as_ref()
+        );
+        assert_eq!(data[FIRST_CREATOR_VERIFIED_OFFSET], 1);
+    }
+
     #[test]
     fn metadata_update() {
         let test_pubkey: Pubkey = Keypair::new().pubkey();
@@ -1452,10 +2280,15 @@ mod cli_tests {
 
 #[cfg(test)]
 mod helper_tests {
-    use crate::MasterEditionCalc;
+    use crate::{
+        output::{CliError, CliErrorDetail},
+        MasterEditionCalc,
+    };
 
-    use super::FetchParse;
+    use super::{error_kind, Error, FetchParse};
     use metaplex_token_metadata::state::Metadata;
+    use serde_json::{json, Value};
+    use solana_cli_output::OutputFormat;
     use solana_sdk::pubkey::Pubkey;
     use spl_token::state::Mint;
     use std::str::FromStr;
@@ -1497,4 +2330,30 @@ mod helper_tests {
         let calc_address = Mint::calc_master_edition(&mint_address);
         assert_eq!(calc_address, correct_address);
     }
+
+    #[test]
+    // It categorizes errors and prints them as text or as a json object with kind and detail.
+    fn json_error() {
+        let err: Error =
+            clap::Error::with_description("Invalid value", clap::ErrorKind::ValueValidation).into();
+        assert_eq!(error_kind(&err), "InvalidArgument");
+
+        let err: Error = "Something went wrong".into();
+        let cli_error = CliError {
+            error: CliErrorDetail {
+                kind: error_kind(&err).to_string(),
+                detail: err.to_string(),
+            },
+        };
+        assert_eq!(
+            OutputFormat::Display.formatted_string(&cli_error),
+            "error: Something went wrong"
+        );
+        let json: Value =
+            serde_json::from_str(&OutputFormat::Json.formatted_string(&cli_error)).unwrap();
+        assert_eq!(
+            json,
+            json!({ "error": { "kind": "Error", "detail": "Something went wrong" } })
+        );
+    }
 }
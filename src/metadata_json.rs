@@ -0,0 +1,1082 @@
+use crate::output::{CliCollectionIssue, CliCollectionPreview, CliMediaTotal};
+use image::{guess_format, load_from_memory_with_format, GenericImageView, ImageFormat};
+use metaplex_token_metadata::state::{
+    Creator, Data, MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+};
+use serde_json::Value;
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    convert::TryFrom,
+    fs::{self, File},
+    io,
+    path::Path,
+    str::FromStr,
+};
+
+// Checks a metadata string against the size of its field in the on-chain metadata account.
+pub(crate) fn check_byte_length(field: &str, value: &str, max_length: usize) -> Result<(), String> {
+    if value.len() > max_length {
+        Err(format!(
+            "Field {} is {} bytes, which exceeds the on-chain limit of {} bytes.",
+            field,
+            value.len(),
+            max_length
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Builds on-chain metadata data from a metadata json file. The uri isn't part of the
+// json since it is the location the json itself is stored at, so it is passed in.
+pub(crate) fn data_from_metadata_json(json: &Value, uri: String) -> Result<Data, String> {
+    let string_field = |field: &str| {
+        json.get(field)
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .ok_or(format!("Missing required string field {}.", field))
+    };
+
+    let seller_fee_basis_points = json
+        .get("seller_fee_basis_points")
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u16::try_from(v).ok())
+        .filter(|v| *v <= 10000)
+        .ok_or("Field seller_fee_basis_points must be an integer from 0 to 10000.")?;
+
+    let creators = match json.get("properties").and_then(|p| p.get("creators")) {
+        Some(Value::Array(creators)) if !creators.is_empty() => Some(
+            creators
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let address = c
+                        .get("address")
+                        .and_then(|a| a.as_str())
+                        .ok_or(format!("Creator [{}] is missing an address.", i))
+                        .and_then(|a| Pubkey::from_str(a).map_err(|e| format!("{}", e)))?;
+                    let share = c
+                        .get("share")
+                        .and_then(|s| s.as_u64())
+                        .and_then(|s| u8::try_from(s).ok())
+                        .filter(|s| *s <= 100)
+                        .ok_or(format!("Creator [{}] share must be from 0 to 100.", i))?;
+                    Ok(Creator {
+                        address,
+                        verified: false,
+                        share,
+                    })
+                })
+                .collect::<Result<Vec<Creator>, String>>()?,
+        ),
+        _ => None,
+    };
+    if let Some(creators) = &creators {
+        check_creators(creators)?;
+    }
+
+    let name = string_field("name")?;
+    let symbol = string_field("symbol")?;
+    check_byte_length("name", &name, MAX_NAME_LENGTH)?;
+    check_byte_length("symbol", &symbol, MAX_SYMBOL_LENGTH)?;
+    check_byte_length("uri", &uri, MAX_URI_LENGTH)?;
+
+    Ok(Data {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators,
+    })
+}
+
+// Checks a metadata json file against the Metaplex token metadata standard and returns
+// a description of each problem found. An empty vec means the file is valid.
+pub(crate) fn validate_metadata_json(json: &Value) -> Vec<String> {
+    let mut issues = Vec::<String>::new();
+
+    for field in ["name", "symbol", "description", "image"] {
+        match json.get(field) {
+            Some(Value::String(_)) => {}
+            Some(_) => issues.push(format!("Field {} must be a string.", field)),
+            None => issues.push(format!("Missing required field {}.", field)),
+        }
+    }
+
+    for (field, max_length) in [("name", MAX_NAME_LENGTH), ("symbol", MAX_SYMBOL_LENGTH)] {
+        if let Some(value) = json.get(field).and_then(|v| v.as_str()) {
+            if let Err(error) = check_byte_length(field, value, max_length) {
+                issues.push(error);
+            }
+        }
+    }
+
+    match json.get("seller_fee_basis_points") {
+        Some(value) => match value.as_u64() {
+            Some(v) if v <= 10000 => {}
+            Some(v) => issues.push(format!(
+                "Basis points must be in range of 0 to 10000, provided: {}",
+                v
+            )),
+            None => issues.push("Field seller_fee_basis_points must be an integer.".to_string()),
+        },
+        None => issues.push("Missing required field seller_fee_basis_points.".to_string()),
+    }
+
+    match json.get("properties") {
+        Some(properties) => {
+            validate_creators(properties.get("creators"), &mut issues);
+            validate_files(properties.get("files"), json.get("image"), &mut issues);
+        }
+        None => issues.push("Missing required field properties.".to_string()),
+    }
+
+    issues
+}
+
+// The token metadata program rejects creator lists that are too long or list an address
+// more than once.
+fn check_creator_list<'a>(addresses: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut issues = Vec::<String>::new();
+    let mut seen = HashSet::<&str>::new();
+    let mut count = 0;
+    for address in addresses {
+        count += 1;
+        if !seen.insert(address) {
+            issues.push(format!(
+                "Creator address {} is listed more than once.",
+                address
+            ));
+        }
+    }
+    if count > MAX_CREATOR_LIMIT {
+        issues.push(format!(
+            "Creators list of {} exceeds the limit of {} creators.",
+            count, MAX_CREATOR_LIMIT
+        ));
+    }
+    issues
+}
+
+// Applies the creator list checks to parsed creators, returning the first problem found.
+pub(crate) fn check_creators(creators: &[Creator]) -> Result<(), String> {
+    let addresses: Vec<String> = creators.iter().map(|c| c.address.to_string()).collect();
+    match check_creator_list(addresses.iter().map(|a| a.as_str()))
+        .into_iter()
+        .next()
+    {
+        Some(issue) => Err(issue),
+        None => Ok(()),
+    }
+}
+
+// Creators must be valid, distinct addresses with shares summing to 100.
+fn validate_creators(creators: Option<&Value>, issues: &mut Vec<String>) {
+    let creators = match creators.map(|c| c.as_array()) {
+        Some(Some(creators)) => creators,
+        Some(None) => {
+            issues.push("Field properties.creators must be an array.".to_string());
+            return;
+        }
+        None => {
+            issues.push("Missing required field properties.creators.".to_string());
+            return;
+        }
+    };
+
+    let mut share_sum: u64 = 0;
+    for (i, creator) in creators.iter().enumerate() {
+        match creator.get("address").and_then(|a| a.as_str()) {
+            Some(address) => {
+                if let Err(error) = Pubkey::from_str(address) {
+                    issues.push(format!("Creator [{}] address {}: {}", i, address, error));
+                }
+            }
+            None => issues.push(format!("Creator [{}] is missing an address.", i)),
+        }
+        match creator.get("share").and_then(|s| s.as_u64()) {
+            Some(share) => share_sum += share,
+            None => issues.push(format!("Creator [{}] is missing an integer share.", i)),
+        }
+    }
+
+    issues.extend(check_creator_list(
+        creators
+            .iter()
+            .filter_map(|c| c.get("address").and_then(|a| a.as_str())),
+    ));
+
+    if !creators.is_empty() && share_sum != 100 {
+        issues.push(format!("Sum of shares of {} must equal 100.", share_sum));
+    }
+}
+
+// Each file entry needs a uri and type, and the image has to be one of the files.
+fn validate_files(files: Option<&Value>, image: Option<&Value>, issues: &mut Vec<String>) {
+    let files = match files.map(|f| f.as_array()) {
+        Some(Some(files)) => files,
+        Some(None) => {
+            issues.push("Field properties.files must be an array.".to_string());
+            return;
+        }
+        None => {
+            issues.push("Missing required field properties.files.".to_string());
+            return;
+        }
+    };
+
+    for (i, file) in files.iter().enumerate() {
+        for field in ["uri", "type"] {
+            if file.get(field).and_then(|v| v.as_str()).is_none() {
+                issues.push(format!("File [{}] is missing {}.", i, field));
+            }
+        }
+    }
+
+    if let Some(image) = image.and_then(|i| i.as_str()) {
+        let image_listed = files
+            .iter()
+            .any(|f| f.get("uri").and_then(|u| u.as_str()) == Some(image));
+        if !files.is_empty() && !image_listed {
+            issues.push(format!(
+                "Image {} is not listed in properties.files.",
+                image
+            ));
+        }
+    }
+}
+
+// Fills a metadata json template from one row of a traits table. Template strings can
+// reference {index} and any column as {<column>}. Columns the template doesn't reference
+// are appended to the attributes as trait types, skipping empty cells.
+pub(crate) fn fill_template(
+    template: &Value,
+    headers: &[String],
+    row: &[String],
+    index: usize,
+) -> Value {
+    fn substitute(value: &Value, replacements: &[(String, String)]) -> Value {
+        match value {
+            Value::String(s) => Value::String(
+                replacements
+                    .iter()
+                    .fold(s.clone(), |s, (from, to)| s.replace(from, to)),
+            ),
+            Value::Array(values) => {
+                Value::Array(values.iter().map(|v| substitute(v, replacements)).collect())
+            }
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), substitute(v, replacements)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+
+    let template_string = template.to_string();
+    let mut replacements = vec![("{index}".to_string(), index.to_string())];
+    let mut attributes = Vec::<Value>::new();
+    for (header, cell) in headers.iter().zip(row) {
+        let placeholder = format!("{{{}}}", header);
+        if template_string.contains(&placeholder) {
+            replacements.push((placeholder, cell.clone()));
+        } else if !cell.is_empty() {
+            attributes.push(serde_json::json!({ "trait_type": header, "value": cell }));
+        }
+    }
+
+    let mut json = substitute(template, &replacements);
+    if !attributes.is_empty() {
+        if let Some(map) = json.as_object_mut() {
+            match map
+                .entry("attributes")
+                .or_insert_with(|| Value::Array(vec![]))
+            {
+                Value::Array(existing) => existing.extend(attributes),
+                other => *other = Value::Array(attributes),
+            }
+        }
+    }
+    json
+}
+
+// Lists the names of the non-hidden files in a directory in sorted order.
+fn list_files(dir: &Path) -> io::Result<Vec<String>> {
+    let mut file_names = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| path.is_file())
+        .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .filter(|name| !name.starts_with('.'))
+        .collect::<Vec<String>>();
+    file_names.sort();
+    Ok(file_names)
+}
+
+// Returns the collection index of a file named <INDEX>.<EXT>.
+fn file_index(path: &Path) -> Option<u64> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.parse::<u64>().ok())
+}
+
+// Counts occurrences of each trait value across metadata files, keyed by trait type.
+pub(crate) fn attribute_counts(jsons: &[Value]) -> BTreeMap<String, BTreeMap<String, usize>> {
+    let mut counts = BTreeMap::<String, BTreeMap<String, usize>>::new();
+    for attributes in jsons
+        .iter()
+        .filter_map(|json| json.get("attributes").and_then(|a| a.as_array()))
+    {
+        for attribute in attributes {
+            let trait_type = match attribute.get("trait_type").and_then(|t| t.as_str()) {
+                Some(trait_type) => trait_type.to_string(),
+                None => continue,
+            };
+            let value = match attribute.get("value") {
+                Some(Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None => continue,
+            };
+            *counts
+                .entry(trait_type)
+                .or_default()
+                .entry(value)
+                .or_default() += 1;
+        }
+    }
+    counts
+}
+
+// Summarizes a collection directory: the number of <INDEX>.json metadata files, bytes by file
+// extension, attribute distribution and the metadata of the lowest indexed item as a sample.
+// Metadata files that fail to parse are counted and listed as issues.
+pub(crate) fn preview_collection(dir: &Path) -> io::Result<CliCollectionPreview> {
+    let mut media = BTreeMap::<String, CliMediaTotal>::new();
+    let mut items = 0;
+    let mut jsons = Vec::<(u64, Value)>::new();
+    let mut issues = Vec::<CliCollectionIssue>::new();
+
+    for name in list_files(dir)? {
+        let path = dir.join(&name);
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let total = media.entry(extension.clone()).or_insert(CliMediaTotal {
+            extension: extension.clone(),
+            files: 0,
+            bytes: 0,
+        });
+        total.files += 1;
+        total.bytes += fs::metadata(&path)?.len();
+
+        if let (Some(index), "json") = (file_index(&path), extension.as_str()) {
+            items += 1;
+            match serde_json::from_reader::<_, Value>(File::open(&path)?) {
+                Ok(json) => jsons.push((index, json)),
+                Err(error) => issues.push(CliCollectionIssue {
+                    path: name,
+                    detail: format!("Unable to parse json: {}", error),
+                }),
+            }
+        }
+    }
+
+    jsons.sort_by_key(|(index, _)| *index);
+    let jsons: Vec<Value> = jsons.into_iter().map(|(_, json)| json).collect();
+
+    Ok(CliCollectionPreview {
+        items,
+        media: media.into_values().collect(),
+        attributes: attribute_counts(&jsons),
+        sample: jsons.into_iter().next(),
+        issues,
+    })
+}
+
+// Finds assets with identical content, which usually indicates a generation bug. Files are
+// grouped by size first so only same sized files are read and hashed.
+pub(crate) fn find_duplicate_assets(dir: &Path) -> io::Result<Vec<CliCollectionIssue>> {
+    let mut by_size = BTreeMap::<u64, Vec<String>>::new();
+    for name in list_files(dir)? {
+        if Path::new(&name).extension().and_then(|e| e.to_str()) != Some("json") {
+            let size = fs::metadata(dir.join(&name))?.len();
+            by_size.entry(size).or_default().push(name);
+        }
+    }
+
+    let mut duplicates = Vec::<CliCollectionIssue>::new();
+    for names in by_size.into_values().filter(|names| names.len() > 1) {
+        let mut by_hash = HashMap::<_, String>::new();
+        for name in names {
+            let content_hash = hash(&fs::read(dir.join(&name))?);
+            match by_hash.get(&content_hash) {
+                Some(original) => duplicates.push(CliCollectionIssue {
+                    detail: format!("Identical content to {}.", original),
+                    path: name,
+                }),
+                None => {
+                    by_hash.insert(content_hash, name);
+                }
+            }
+        }
+    }
+    duplicates.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(duplicates)
+}
+
+// Optional limits applied by check_images.
+#[derive(Default)]
+pub(crate) struct ImageLimits {
+    pub(crate) max_width: Option<usize>,
+    pub(crate) max_height: Option<usize>,
+    pub(crate) max_bytes: Option<u64>,
+}
+
+fn expected_image_format(extension: &str) -> Option<ImageFormat> {
+    match extension {
+        "png" => Some(ImageFormat::Png),
+        "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+        "gif" => Some(ImageFormat::Gif),
+        "webp" => Some(ImageFormat::WebP),
+        "bmp" => Some(ImageFormat::Bmp),
+        _ => None,
+    }
+}
+
+// Checks the images in a collection directory: contents match the file extension, images
+// decode fully so truncated or corrupt files are caught, dimensions and file sizes are within
+// the limits and all images share the aspect ratio of the first one. Other media types are
+// skipped.
+pub(crate) fn check_images(
+    dir: &Path,
+    limits: &ImageLimits,
+) -> io::Result<Vec<CliCollectionIssue>> {
+    let mut issues = Vec::<CliCollectionIssue>::new();
+    let mut issue = |path: &str, detail: String| {
+        issues.push(CliCollectionIssue {
+            path: path.to_string(),
+            detail,
+        })
+    };
+
+    let mut first_image: Option<(String, usize, usize)> = None;
+    for name in list_files(dir)? {
+        let extension = Path::new(&name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let expected_format = match expected_image_format(&extension) {
+            Some(expected_format) => expected_format,
+            None => continue,
+        };
+
+        let bytes = fs::read(dir.join(&name))?;
+        if let Some(max_bytes) = limits.max_bytes {
+            if bytes.len() as u64 > max_bytes {
+                issue(
+                    &name,
+                    format!(
+                        "File size of {} bytes exceeds the limit of {} bytes.",
+                        bytes.len(),
+                        max_bytes
+                    ),
+                );
+            }
+        }
+
+        let actual_format = match guess_format(&bytes) {
+            Ok(actual_format) => actual_format,
+            Err(error) => {
+                issue(&name, format!("Unable to read image: {}", error));
+                continue;
+            }
+        };
+        if actual_format != expected_format {
+            issue(
+                &name,
+                format!(
+                    "Extension .{} does not match {:?} content.",
+                    extension, actual_format
+                ),
+            );
+        }
+
+        let (width, height) = match load_from_memory_with_format(&bytes, actual_format) {
+            Ok(image) => {
+                let (width, height) = image.dimensions();
+                (width as usize, height as usize)
+            }
+            Err(error) => {
+                issue(&name, format!("Unable to decode image: {}", error));
+                continue;
+            }
+        };
+        if limits.max_width.is_some_and(|max| width > max)
+            || limits.max_height.is_some_and(|max| height > max)
+        {
+            issue(
+                &name,
+                format!(
+                    "Dimensions of {}x{} exceed the limit of {}x{}.",
+                    width,
+                    height,
+                    limits
+                        .max_width
+                        .map_or("any".to_string(), |w| w.to_string()),
+                    limits
+                        .max_height
+                        .map_or("any".to_string(), |h| h.to_string()),
+                ),
+            );
+        }
+
+        match &first_image {
+            Some((first_name, first_width, first_height)) => {
+                if width * first_height != height * first_width {
+                    issue(
+                        &name,
+                        format!(
+                            "Aspect ratio of {}x{} differs from {} at {}x{}.",
+                            width, height, first_name, first_width, first_height
+                        ),
+                    );
+                }
+            }
+            None => first_image = Some((name.clone(), width, height)),
+        }
+    }
+
+    Ok(issues)
+}
+
+// Checks a collection directory in the standard <INDEX>.<EXT> / <INDEX>.json layout: indices
+// are contiguous from zero, every asset has a metadata file and vice versa, names are unique,
+// each metadata image field references a sibling asset and any further assets for an index
+// are referenced by its animation_url or properties.files. Returns the number of
+// metadata files and the issues found.
+pub(crate) fn check_collection(dir: &Path) -> io::Result<(usize, Vec<CliCollectionIssue>)> {
+    let mut issues = Vec::<CliCollectionIssue>::new();
+    let mut issue = |path: &str, detail: String| {
+        issues.push(CliCollectionIssue {
+            path: path.to_string(),
+            detail,
+        })
+    };
+
+    let mut assets = BTreeMap::<u64, Vec<String>>::new();
+    let mut metadata = BTreeMap::<u64, String>::new();
+    for name in list_files(dir)? {
+        let path = Path::new(&name);
+        match (file_index(path), path.extension().and_then(|e| e.to_str())) {
+            (Some(index), Some("json")) => {
+                metadata.insert(index, name);
+            }
+            (Some(index), Some(_)) => assets.entry(index).or_default().push(name),
+            _ => issue(
+                &name,
+                "File name is not of the form <INDEX>.<EXTENSION>.".to_string(),
+            ),
+        }
+    }
+
+    let indices: BTreeSet<u64> = assets.keys().chain(metadata.keys()).cloned().collect();
+    if let Some(max_index) = indices.iter().next_back() {
+        for index in (0..*max_index).filter(|i| !indices.contains(i)) {
+            issue("", format!("Missing index {}.", index));
+        }
+    }
+
+    let mut names = HashMap::<String, String>::new();
+    for index in indices {
+        let index_assets = assets.get(&index).cloned().unwrap_or_default();
+        let metadata_name = match metadata.get(&index) {
+            Some(metadata_name) => metadata_name,
+            None => {
+                for asset in &index_assets {
+                    issue(asset, "No metadata file for asset.".to_string());
+                }
+                continue;
+            }
+        };
+        if index_assets.is_empty() {
+            issue(metadata_name, "No asset for metadata file.".to_string());
+        }
+
+        let json: Value = match serde_json::from_reader(File::open(dir.join(metadata_name))?) {
+            Ok(json) => json,
+            Err(error) => {
+                issue(metadata_name, format!("Unable to parse json: {}", error));
+                continue;
+            }
+        };
+
+        if let Some(name) = json.get("name").and_then(|n| n.as_str()) {
+            if let Some(other) = names.get(name) {
+                issue(
+                    metadata_name,
+                    format!("Name {} is also used by {}.", name, other),
+                );
+            } else {
+                names.insert(name.to_string(), metadata_name.clone());
+            }
+        }
+
+        let image = json.get("image").and_then(|i| i.as_str());
+        match image {
+            Some(image) => match index_assets.len() {
+                0 => {}
+                1 if image != index_assets[0] => issue(
+                    metadata_name,
+                    format!(
+                        "Image {} does not reference sibling asset {}.",
+                        image, index_assets[0]
+                    ),
+                ),
+                _ if !index_assets.iter().any(|asset| asset == image) => issue(
+                    metadata_name,
+                    format!(
+                        "Image {} does not reference any of the sibling assets {}.",
+                        image,
+                        index_assets.join(", ")
+                    ),
+                ),
+                _ => {}
+            },
+            None => issue(metadata_name, "Missing required field image.".to_string()),
+        }
+
+        // Rich media items have an image plus e.g. an animation, which are fine as long as
+        // the metadata references them.
+        if index_assets.len() > 1 {
+            let files = json
+                .get("properties")
+                .and_then(|p| p.get("files"))
+                .and_then(|f| f.as_array());
+            let referenced: HashSet<&str> = image
+                .into_iter()
+                .chain(json.get("animation_url").and_then(|a| a.as_str()))
+                .chain(
+                    files
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|f| f.get("uri").and_then(|u| u.as_str())),
+                )
+                .collect();
+            for asset in index_assets
+                .iter()
+                .filter(|asset| !referenced.contains(asset.as_str()))
+            {
+                issue(
+                    asset,
+                    format!("Asset is not referenced by {}.", metadata_name),
+                );
+            }
+        }
+    }
+
+    Ok((metadata.len(), issues))
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::{
+        attribute_counts, check_collection, check_images, data_from_metadata_json, fill_template,
+        find_duplicate_assets, preview_collection, validate_metadata_json, ImageLimits,
+    };
+    use image::{DynamicImage, ImageOutputFormat};
+    use serde_json::{json, Value};
+    use solana_sdk::{
+        pubkey::Pubkey,
+        signer::{keypair::Keypair, Signer},
+    };
+    use std::{fs, io::Cursor};
+
+    fn test_metadata(creator: &Pubkey) -> Value {
+        let co_creator = Keypair::new().pubkey();
+        json!({
+            "name": "Testeroni #0",
+            "symbol": "TEST",
+            "description": "A test nft.",
+            "seller_fee_basis_points": 500,
+            "image": "0.png",
+            "properties": {
+                "files": [{ "uri": "0.png", "type": "image/png" }],
+                "category": "image",
+                "creators": [
+                    { "address": creator.to_string(), "share": 60 },
+                    { "address": co_creator.to_string(), "share": 40 }
+                ]
+            }
+        })
+    }
+
+    #[test]
+    // It passes for metadata that follows the standard.
+    fn valid_metadata() {
+        let creator = Keypair::new().pubkey();
+        assert!(validate_metadata_json(&test_metadata(&creator)).is_empty());
+    }
+
+    #[test]
+    // It reports missing required fields.
+    fn missing_fields() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata.as_object_mut().unwrap().remove("symbol");
+        let issues = validate_metadata_json(&metadata);
+        assert_eq!(issues, vec!["Missing required field symbol."]);
+    }
+
+    #[test]
+    // It fails if name or symbol don't fit in the metadata account.
+    fn name_and_symbol_length() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata["name"] = json!("x".repeat(33));
+        metadata["symbol"] = json!("TESTERONIS");
+        let issues = validate_metadata_json(&metadata);
+        assert_eq!(
+            issues,
+            vec!["Field name is 33 bytes, which exceeds the on-chain limit of 32 bytes."]
+        );
+        assert!(data_from_metadata_json(&metadata, String::new()).is_err());
+    }
+
+    #[test]
+    // It fails if basis points are out of range or shares don't sum to 100.
+    fn fees_and_shares() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata["seller_fee_basis_points"] = json!(10001);
+        metadata["properties"]["creators"][0]["share"] = json!(61);
+        let issues = validate_metadata_json(&metadata);
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[test]
+    // It fails if a creator address is not valid base58.
+    fn creator_address() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata["properties"]["creators"][1]["address"] = json!("bogus_pubkey");
+        let issues = validate_metadata_json(&metadata);
+        assert_eq!(issues.len(), 1);
+    }
+
+    #[test]
+    // It fails if a creator is listed twice, which the program rejects.
+    fn duplicate_creator() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata["properties"]["creators"][1]["address"] = json!(creator.to_string());
+        let issues = validate_metadata_json(&metadata);
+        assert_eq!(
+            issues,
+            vec![format!(
+                "Creator address {} is listed more than once.",
+                creator
+            )]
+        );
+    }
+
+    #[test]
+    // It fails if there are more creators than the metadata account holds.
+    fn too_many_creators() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        let creators: Vec<Value> = [20, 20, 20, 20, 10, 10]
+            .iter()
+            .map(|share| json!({ "address": Keypair::new().pubkey().to_string(), "share": share }))
+            .collect();
+        metadata["properties"]["creators"] = json!(creators);
+        let issues = validate_metadata_json(&metadata);
+        assert_eq!(
+            issues,
+            vec!["Creators list of 6 exceeds the limit of 5 creators."]
+        );
+    }
+
+    #[test]
+    // It fails if the image isn't among the files.
+    fn image_not_in_files() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata["image"] = json!("1.png");
+        let issues = validate_metadata_json(&metadata);
+        assert_eq!(
+            issues,
+            vec!["Image 1.png is not listed in properties.files."]
+        );
+    }
+
+    #[test]
+    // It reads name, symbol, fees and creators from the json and takes the uri separately.
+    fn data_from_json() {
+        let creator = Keypair::new().pubkey();
+        let data =
+            data_from_metadata_json(&test_metadata(&creator), "ipfs://testeroni".to_string())
+                .unwrap();
+        assert_eq!(data.name, "Testeroni #0");
+        assert_eq!(data.symbol, "TEST");
+        assert_eq!(data.uri, "ipfs://testeroni");
+        assert_eq!(data.seller_fee_basis_points, 500);
+        let creators = data.creators.unwrap();
+        assert_eq!(creators.len(), 2);
+        assert_eq!(creators[0].address, creator);
+        assert_eq!(creators[0].share, 60);
+    }
+
+    #[test]
+    // It fails if a creator address is not valid.
+    fn data_from_json_bad_creator() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata["properties"]["creators"][0]["address"] = json!("bogus_pubkey");
+        assert!(data_from_metadata_json(&metadata, String::new()).is_err());
+    }
+
+    #[test]
+    // It fails if a creator is listed twice.
+    fn data_from_json_duplicate_creator() {
+        let creator = Keypair::new().pubkey();
+        let mut metadata = test_metadata(&creator);
+        metadata["properties"]["creators"][1]["address"] = json!(creator.to_string());
+        assert_eq!(
+            data_from_metadata_json(&metadata, String::new()).unwrap_err(),
+            format!("Creator address {} is listed more than once.", creator)
+        );
+    }
+
+    #[test]
+    // It reports gaps, unpaired files, duplicate names and mismatched images.
+    fn collection_issues() {
+        let dir = std::env::temp_dir().join(format!("collection_{}", Keypair::new().pubkey()));
+        fs::create_dir_all(&dir).unwrap();
+        let write_metadata = |index: u64, name: &str, image: &str| {
+            let json = json!({ "name": name, "image": image });
+            fs::write(dir.join(format!("{}.json", index)), json.to_string()).unwrap();
+        };
+        for file in ["0.png", "1.png", "3.png", "cover.png"] {
+            fs::write(dir.join(file), []).unwrap();
+        }
+        write_metadata(0, "Testeroni #0", "0.png");
+        write_metadata(1, "Testeroni #0", "image.png");
+        write_metadata(4, "Testeroni #4", "4.png");
+
+        let (items, issues) = check_collection(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let details: Vec<(&str, &str)> = issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.detail.as_str()))
+            .collect();
+        assert_eq!(items, 3);
+        assert_eq!(
+            details,
+            vec![
+                (
+                    "cover.png",
+                    "File name is not of the form <INDEX>.<EXTENSION>."
+                ),
+                ("", "Missing index 2."),
+                ("1.json", "Name Testeroni #0 is also used by 0.json."),
+                (
+                    "1.json",
+                    "Image image.png does not reference sibling asset 1.png."
+                ),
+                ("3.png", "No metadata file for asset."),
+                ("4.json", "No asset for metadata file."),
+            ]
+        );
+    }
+
+    #[test]
+    // It accepts extra assets referenced by the metadata and reports unreferenced ones.
+    fn rich_media_assets() {
+        let dir = std::env::temp_dir().join(format!("rich_media_{}", Keypair::new().pubkey()));
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["0.png", "0.mp4", "1.png", "1.glb", "2.png", "2.gif"] {
+            fs::write(dir.join(file), []).unwrap();
+        }
+        let metadata = [
+            json!({ "name": "Testeroni #0", "image": "0.png", "animation_url": "0.mp4" }),
+            json!({
+                "name": "Testeroni #1",
+                "image": "1.png",
+                "properties": { "files": [{ "uri": "1.png" }, { "uri": "1.glb" }] }
+            }),
+            json!({ "name": "Testeroni #2", "image": "2.png" }),
+        ];
+        for (index, json) in metadata.iter().enumerate() {
+            fs::write(dir.join(format!("{}.json", index)), json.to_string()).unwrap();
+        }
+
+        let (items, issues) = check_collection(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let details: Vec<(&str, &str)> = issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.detail.as_str()))
+            .collect();
+        assert_eq!(items, 3);
+        assert_eq!(
+            details,
+            vec![("2.gif", "Asset is not referenced by 2.json.")]
+        );
+    }
+
+    #[test]
+    // It counts trait values by trait type, including numeric values.
+    fn attribute_distribution() {
+        let jsons = vec![
+            json!({ "attributes": [
+                { "trait_type": "Background", "value": "Blue" },
+                { "trait_type": "Level", "value": 1 }
+            ]}),
+            json!({ "attributes": [
+                { "trait_type": "Background", "value": "Blue" },
+                { "trait_type": "Level", "value": 2 }
+            ]}),
+            json!({ "name": "No attributes" }),
+        ];
+        let counts = attribute_counts(&jsons);
+        assert_eq!(counts["Background"]["Blue"], 2);
+        assert_eq!(counts["Level"]["1"], 1);
+        assert_eq!(counts["Level"]["2"], 1);
+    }
+
+    #[test]
+    // It counts indexed metadata files, including unparseable ones, which it lists as issues.
+    fn preview_items() {
+        let dir = std::env::temp_dir().join(format!("preview_{}", Keypair::new().pubkey()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("0.png"), b"test").unwrap();
+        fs::write(
+            dir.join("1.json"),
+            json!({ "name": "Testeroni #1" }).to_string(),
+        )
+        .unwrap();
+        fs::write(dir.join("2.json"), b"{").unwrap();
+        fs::write(
+            dir.join("collection.json"),
+            json!({ "name": "Testeronis" }).to_string(),
+        )
+        .unwrap();
+
+        let preview = preview_collection(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(preview.items, 2);
+        assert_eq!(preview.sample.unwrap()["name"], "Testeroni #1");
+        assert_eq!(preview.issues.len(), 1);
+        assert_eq!(preview.issues[0].path, "2.json");
+    }
+
+    #[test]
+    // It substitutes placeholders and turns unreferenced columns into attributes.
+    fn template_from_row() {
+        let template = json!({
+            "name": "Testeroni #{index}",
+            "image": "{index}.png",
+            "description": "{description}",
+            "attributes": [{ "trait_type": "Edition", "value": "Genesis" }],
+            "properties": { "files": [{ "uri": "{index}.png", "type": "image/png" }] }
+        });
+        let headers = vec![
+            "description".to_string(),
+            "Background".to_string(),
+            "Hat".to_string(),
+        ];
+        let row = vec!["A test nft.".to_string(), "Blue".to_string(), String::new()];
+
+        let json = fill_template(&template, &headers, &row, 7);
+        assert_eq!(
+            json,
+            json!({
+                "name": "Testeroni #7",
+                "image": "7.png",
+                "description": "A test nft.",
+                "attributes": [
+                    { "trait_type": "Edition", "value": "Genesis" },
+                    { "trait_type": "Background", "value": "Blue" }
+                ],
+                "properties": { "files": [{ "uri": "7.png", "type": "image/png" }] }
+            })
+        );
+    }
+
+    // Encodes a blank png with the given dimensions.
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::<u8>::new();
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    // It reports mismatched content, oversized, truncated and unreadable images and
+    // differing aspect ratios.
+    fn image_issues() {
+        let dir = std::env::temp_dir().join(format!("images_{}", Keypair::new().pubkey()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("0.png"), png(100, 100)).unwrap();
+        fs::write(dir.join("1.jpg"), png(100, 100)).unwrap();
+        fs::write(dir.join("2.png"), png(200, 100)).unwrap();
+        fs::write(dir.join("3.mp4"), []).unwrap();
+        fs::write(dir.join("4.png"), &png(100, 100)[..33]).unwrap();
+        fs::write(dir.join("5.png"), b"test").unwrap();
+
+        let limits = ImageLimits {
+            max_width: Some(150),
+            ..ImageLimits::default()
+        };
+        let issues = check_images(&dir, &limits).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Decoder messages vary, so only compare what comes before them.
+        let details: Vec<(&str, &str)> = issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.detail.split(':').next().unwrap()))
+            .collect();
+        assert_eq!(
+            details,
+            vec![
+                ("1.jpg", "Extension .jpg does not match Png content."),
+                (
+                    "2.png",
+                    "Dimensions of 200x100 exceed the limit of 150xany."
+                ),
+                (
+                    "2.png",
+                    "Aspect ratio of 200x100 differs from 0.png at 100x100."
+                ),
+                ("4.png", "Unable to decode image"),
+                ("5.png", "Unable to read image"),
+            ]
+        );
+    }
+
+    #[test]
+    // It reports assets with the same content, but not ones of the same size.
+    fn duplicate_assets() {
+        let dir = std::env::temp_dir().join(format!("duplicates_{}", Keypair::new().pubkey()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("0.png"), b"test").unwrap();
+        fs::write(dir.join("1.png"), b"tset").unwrap();
+        fs::write(dir.join("2.png"), b"test").unwrap();
+        fs::write(dir.join("0.json"), b"test").unwrap();
+
+        let duplicates = find_duplicate_assets(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].path, "2.png");
+        assert_eq!(duplicates[0].detail, "Identical content to 0.png.");
+    }
+}
@@ -1,18 +1,18 @@
-use crate::config::Config;
 use console::Emoji;
 use metaplex_token_metadata::state::{Creator, Data, Key, Metadata};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use solana_account_decoder::parse_token::{UiMint, UiTokenAmount};
 use solana_cli_output::{display::writeln_name_value, OutputFormat, QuietDisplay, VerboseDisplay};
-use std::fmt;
+use solana_sdk::native_token::lamports_to_sol;
+use std::{collections::BTreeMap, fmt, io};
 
 pub type StringAmount = String;
 
-#[allow(dead_code)]
 static WARNING: Emoji = Emoji("⚠️", "!");
 
-pub(crate) fn println_display(config: &Config, message: String) {
-    match config.output_format {
+pub(crate) fn println_display(output_format: &OutputFormat, message: String) {
+    match output_format {
         OutputFormat::Display | OutputFormat::DisplayVerbose => {
             println!("{}", message);
         }
@@ -20,6 +20,40 @@ pub(crate) fn println_display(config: &Config, message: String) {
     }
 }
 
+// Prints errors as plain text for display output, or as a structured object
+// for json output so that wrapping tools can parse them from stderr.
+pub(crate) fn eprintln_error(output_format: &OutputFormat, kind: &str, detail: String) {
+    let cli_error = CliError {
+        error: CliErrorDetail {
+            kind: kind.to_string(),
+            detail,
+        },
+    };
+    eprintln!("{}", output_format.formatted_string(&cli_error));
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliError {
+    pub(crate) error: CliErrorDetail,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliErrorDetail {
+    pub(crate) kind: String,
+    pub(crate) detail: String,
+}
+
+impl QuietDisplay for CliError {}
+impl VerboseDisplay for CliError {}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error: {}", self.error.detail)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CliTokenAmount {
@@ -226,3 +260,291 @@ impl fmt::Display for UiCreator {
         Ok(())
     }
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliMetadataValidation {
+    pub(crate) path: String,
+    pub(crate) issues: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliMetadataValidationReport {
+    pub(crate) valid: usize,
+    pub(crate) invalid: usize,
+    pub(crate) files: Vec<CliMetadataValidation>,
+}
+
+impl QuietDisplay for CliMetadataValidationReport {}
+impl VerboseDisplay for CliMetadataValidationReport {
+    fn write_str(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        for file in &self.files {
+            writeln!(w, "{}: {}", file.path, file.issues.len())?;
+            for issue in &file.issues {
+                writeln!(w, "  - {}", issue)?;
+            }
+        }
+        writeln!(w, "Valid: {}", self.valid)?;
+        writeln!(w, "Invalid: {}", self.invalid)
+    }
+}
+
+impl fmt::Display for CliMetadataValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for file in self.files.iter().filter(|file| !file.issues.is_empty()) {
+            writeln!(f, "{}:", file.path)?;
+            for issue in &file.issues {
+                writeln!(f, "  - {}", issue)?;
+            }
+        }
+        writeln!(f, "Valid: {}", self.valid)?;
+        writeln!(f, "Invalid: {}", self.invalid)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliCollectionIssue {
+    // Empty for issues that aren't about a single file, such as a missing index.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) path: String,
+    pub(crate) detail: String,
+}
+
+impl fmt::Display for CliCollectionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.detail)
+        } else {
+            write!(f, "{}: {}", self.path, self.detail)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliCollectionCheck {
+    pub(crate) items: usize,
+    pub(crate) issues: Vec<CliCollectionIssue>,
+    pub(crate) warnings: Vec<CliCollectionIssue>,
+}
+
+impl QuietDisplay for CliCollectionCheck {}
+impl VerboseDisplay for CliCollectionCheck {}
+
+impl fmt::Display for CliCollectionCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "{} {}", WARNING, warning)?;
+        }
+        writeln!(f, "Items: {}", self.items)?;
+        writeln!(f, "Issues: {}", self.issues.len())?;
+        writeln!(f, "Warnings: {}", self.warnings.len())?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliMediaTotal {
+    pub(crate) extension: String,
+    pub(crate) files: usize,
+    pub(crate) bytes: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliCollectionPreview {
+    pub(crate) items: usize,
+    pub(crate) media: Vec<CliMediaTotal>,
+    pub(crate) attributes: BTreeMap<String, BTreeMap<String, usize>>,
+    pub(crate) sample: Option<Value>,
+    pub(crate) issues: Vec<CliCollectionIssue>,
+}
+
+impl QuietDisplay for CliCollectionPreview {}
+impl VerboseDisplay for CliCollectionPreview {}
+
+impl fmt::Display for CliCollectionPreview {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Items: {}", self.items)?;
+        for issue in &self.issues {
+            writeln!(f, "{} {}", WARNING, issue)?;
+        }
+        writeln!(f, "Media:")?;
+        for total in &self.media {
+            writeln!(
+                f,
+                "  {}: {} files, {} bytes",
+                total.extension, total.files, total.bytes
+            )?;
+        }
+        writeln!(f, "Attributes:")?;
+        for (trait_type, values) in &self.attributes {
+            writeln!(f, "  {}:", trait_type)?;
+            for (value, count) in values {
+                writeln!(f, "    {}: {}", value, count)?;
+            }
+        }
+        if let Some(sample) = &self.sample {
+            writeln!(f, "Sample:")?;
+            for field in ["name", "symbol", "image", "description"] {
+                if let Some(value) = sample.get(field).and_then(|v| v.as_str()) {
+                    writeln!(f, "  {}: {}", field, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTraitRarity {
+    pub(crate) trait_type: String,
+    pub(crate) value: String,
+    pub(crate) count: usize,
+    pub(crate) percentage: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliRarityReport {
+    pub(crate) items: usize,
+    pub(crate) traits: Vec<CliTraitRarity>,
+}
+
+impl CliRarityReport {
+    pub(crate) fn write_csv<W: io::Write>(&self, writer: W) -> csv::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["trait_type", "value", "count", "percentage"])?;
+        for t in &self.traits {
+            writer.write_record([
+                t.trait_type.clone(),
+                t.value.clone(),
+                t.count.to_string(),
+                format!("{:.2}", t.percentage),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliRarityReport {}
+impl VerboseDisplay for CliRarityReport {}
+
+impl fmt::Display for CliRarityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let trait_width = self
+            .traits
+            .iter()
+            .map(|t| t.trait_type.len())
+            .chain(std::iter::once("Trait".len()))
+            .max()
+            .unwrap_or_default();
+        let value_width = self
+            .traits
+            .iter()
+            .map(|t| t.value.len())
+            .chain(std::iter::once("Value".len()))
+            .max()
+            .unwrap_or_default();
+
+        writeln!(f, "Items: {}", self.items)?;
+        writeln!(
+            f,
+            "{:<tw$}  {:<vw$}  {:>6}  {:>8}",
+            "Trait",
+            "Value",
+            "Count",
+            "Percent",
+            tw = trait_width,
+            vw = value_width
+        )?;
+        for t in &self.traits {
+            writeln!(
+                f,
+                "{:<tw$}  {:<vw$}  {:>6}  {:>7.2}%",
+                t.trait_type,
+                t.value,
+                t.count,
+                t.percentage,
+                tw = trait_width,
+                vw = value_width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Serializes as a bare json array of mint addresses, the hash list format used by
+// marketplaces.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct CliHashList {
+    pub(crate) mints: Vec<String>,
+}
+
+impl QuietDisplay for CliHashList {}
+impl VerboseDisplay for CliHashList {}
+
+impl fmt::Display for CliHashList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for mint in &self.mints {
+            writeln!(f, "{}", mint)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliSimulation {
+    pub(crate) transaction: usize,
+    pub(crate) program_ids: Vec<String>,
+    pub(crate) fee: u64,
+    // Earlier transactions whose accounts this one uses. It isn't simulated when non-empty.
+    pub(crate) depends_on: Vec<usize>,
+    pub(crate) error: Option<String>,
+    pub(crate) logs: Vec<String>,
+}
+
+impl QuietDisplay for CliSimulation {}
+impl VerboseDisplay for CliSimulation {
+    fn write_str(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        write!(w, "{}", self)?;
+        writeln!(w, "  Logs:")?;
+        for log in &self.logs {
+            writeln!(w, "    {}", log)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CliSimulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Transaction {}:", self.transaction)?;
+        writeln!(f, "  Programs: {}", self.program_ids.join(", "))?;
+        writeln!(f, "  Fee: {} SOL", lamports_to_sol(self.fee))?;
+        match &self.error {
+            Some(error) => writeln!(f, "  Error: {}", error)?,
+            None if !self.depends_on.is_empty() => writeln!(
+                f,
+                "  Result: Not simulated, depends on transaction {}",
+                self.depends_on
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?,
+            None => writeln!(f, "  Result: Ok")?,
+        }
+        Ok(())
+    }
+}